@@ -1,21 +1,91 @@
+use crate::config;
 use crate::event;
 use crate::prelude::*;
 use crate::state;
+use std::time::{Duration, Instant};
+
+/// A cached value, keyed by playlist id in `Client::cache`; albums and
+/// artists are not cached
+#[derive(Clone, Debug, PartialEq)]
+enum CachedValue {
+    Playlist(playlist::FullPlaylist),
+    PlaylistTracks(Vec<playlist::PlaylistTrack>),
+}
+
+/// An in-memory, per-entity response cache used to avoid redundant API calls
+/// when the user navigates back and forth between already-fetched entities.
+/// Keys are namespaced by value kind (e.g. `playlist:{id}` vs
+/// `playlist_tracks:{id}`) so two different cached shapes for the same id
+/// don't collide in the same map.
+struct Cache {
+    ttl: Duration,
+    entries: std::sync::Mutex<std::collections::HashMap<String, (Instant, CachedValue)>>,
+}
+
+impl Cache {
+    fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Default::default(),
+        }
+    }
+
+    fn get(&self, id: &str) -> Option<CachedValue> {
+        let entries = self.entries.lock().unwrap();
+        match entries.get(id) {
+            Some((inserted_at, value)) if inserted_at.elapsed() < self.ttl => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    fn set(&self, id: String, value: CachedValue) {
+        self.entries.lock().unwrap().insert(id, (Instant::now(), value));
+    }
+
+    fn invalidate(&self, id: &str) {
+        self.entries.lock().unwrap().remove(id);
+    }
+}
 
 /// A spotify client
 pub struct Client {
     spotify: Spotify,
     http: reqwest::Client,
     oauth: SpotifyOAuth,
+    cache: Cache,
+    #[cfg(feature = "metrics")]
+    metrics: std::sync::Arc<crate::metrics::Metrics>,
+}
+
+/// Results of a remote catalog search, grouped by result type
+#[derive(Default, Debug, Clone)]
+pub struct SearchResults {
+    pub tracks: Vec<track::FullTrack>,
+    pub albums: Vec<album::SimplifiedAlbum>,
+    pub artists: Vec<artist::FullArtist>,
+    pub playlists: Vec<playlist::SimplifiedPlaylist>,
 }
 
 impl Client {
     /// returns the new `Client`
-    pub fn new(oauth: SpotifyOAuth) -> Self {
+    pub fn new(oauth: SpotifyOAuth, config: &config::Config) -> Self {
+        #[cfg(feature = "metrics")]
+        let metrics = std::sync::Arc::new(crate::metrics::Metrics::default());
+        #[cfg(feature = "metrics")]
+        if let Some(metrics_config) = config.metrics.as_ref() {
+            metrics.clone().spawn_pusher(
+                metrics_config.pushgateway_url.clone(),
+                std::time::Duration::from_secs(metrics_config.push_interval_secs),
+            );
+        }
+
         Self {
             spotify: Spotify::default(),
             http: reqwest::Client::new(),
             oauth,
+            cache: Cache::new(Duration::from_secs(config.cache.ttl_secs)),
+            #[cfg(feature = "metrics")]
+            metrics,
         }
     }
 
@@ -25,19 +95,31 @@ impl Client {
         state: &state::SharedState,
         event: event::Event,
     ) -> Result<()> {
+        #[cfg(feature = "metrics")]
+        self.metrics.record_event();
+
         match event {
             event::Event::RefreshToken => {
                 self.refresh_token().await?;
             }
             event::Event::GetCurrentPlaybackContext => {
                 let context = self.get_current_playback().await?;
+                #[cfg(feature = "metrics")]
+                self.metrics.set_playback_state(
+                    context.as_ref().and_then(|c| c.item.as_ref()).and_then(|item| item.id.clone()),
+                    context.as_ref().map_or(false, |c| c.is_playing),
+                );
                 state.write().unwrap().current_playback_context = context;
             }
             event::Event::NextSong => {
                 self.next_track().await?;
+                #[cfg(feature = "metrics")]
+                self.metrics.record_track_played();
             }
             event::Event::PreviousSong => {
                 self.previous_track().await?;
+                #[cfg(feature = "metrics")]
+                self.metrics.record_track_played();
             }
             event::Event::ResumePause => {
                 let state = state.read().unwrap();
@@ -62,6 +144,45 @@ impl Client {
                 let tracks = self.get_current_playlist_tracks(state).await?;
                 state.write().unwrap().current_playlist_tracks = Some(tracks);
             }
+            event::Event::GetDevices => {
+                let devices = self.get_devices().await?;
+                state.write().unwrap().available_devices = devices;
+            }
+            event::Event::TransferPlayback(device_id, play) => {
+                self.transfer_playback(&device_id, Some(play)).await?;
+            }
+            event::Event::SeekTrack(position_ms) => {
+                self.seek_track(position_ms).await?;
+            }
+            event::Event::SeekRelative(delta_ms) => {
+                let state = state.read().unwrap();
+                self.seek_relative(&state, delta_ms).await?;
+            }
+            event::Event::Search(query) => {
+                let results = self.search(&query).await?;
+                state.write().unwrap().search_results = Some(results);
+            }
+            event::Event::PlaySelected(uri) => {
+                self.play_selected(&uri).await?;
+            }
+            event::Event::GetRecommendations {
+                seed_tracks,
+                seed_artists,
+                seed_genres,
+            } => {
+                let (id, tracks) = self
+                    .get_recommendations(seed_tracks, seed_artists, seed_genres)
+                    .await?;
+                state
+                    .write()
+                    .unwrap()
+                    .context_cache
+                    .insert(id, state::Context::Tracks(tracks));
+            }
+            event::Event::InvalidateCache(id) => {
+                self.cache.invalidate(&format!("playlist:{id}"));
+                self.cache.invalidate(&format!("playlist_tracks:{id}"));
+            }
         }
         Ok(())
     }
@@ -95,25 +216,156 @@ impl Client {
         &self,
         state: &state::SharedState,
     ) -> Result<Vec<playlist::PlaylistTrack>> {
-        let mut tracks: Vec<playlist::PlaylistTrack> = vec![];
-        if let Some(playlist) = state.read().unwrap().current_playlist.as_ref() {
-            tracks = playlist.tracks.items.clone();
-            let mut next = playlist.tracks.next.clone();
-            while let Some(url) = next.as_ref() {
-                log::info!("url: {}", url);
-                let mut paged_tracks = self
-                    .internal_call::<page::Page<playlist::PlaylistTrack>>(url)
-                    .await?;
-                log::info!("paged_tracks: {:?}", paged_tracks);
-                tracks.append(&mut paged_tracks.items);
-                next = paged_tracks.next;
-            }
+        let playlist = state
+            .read()
+            .unwrap()
+            .current_playlist
+            .as_ref()
+            .map(|playlist| (playlist.id.clone(), playlist.tracks.clone()));
+        let (id, tracks) = match playlist {
+            Some(playlist) => playlist,
+            None => return Ok(vec![]),
+        };
+
+        let cache_key = format!("playlist_tracks:{id}");
+        if let Some(CachedValue::PlaylistTracks(tracks)) = self.cache.get(&cache_key) {
+            return Ok(tracks);
         }
+
+        let tracks = self.fetch_all_pages(tracks).await?;
+        self.cache
+            .set(cache_key, CachedValue::PlaylistTracks(tracks.clone()));
         Ok(tracks)
     }
 
     async fn get_playlist(&self, playlist_id: &str) -> Result<playlist::FullPlaylist> {
-        Self::handle_rspotify_result(self.spotify.playlist(playlist_id, None, None).await)
+        let cache_key = format!("playlist:{playlist_id}");
+        if let Some(CachedValue::Playlist(playlist)) = self.cache.get(&cache_key) {
+            return Ok(playlist);
+        }
+
+        let playlist =
+            Self::handle_rspotify_result(self.spotify.playlist(playlist_id, None, None).await)?;
+        self.cache
+            .set(cache_key, CachedValue::Playlist(playlist.clone()));
+        Ok(playlist)
+    }
+
+    /// gets the list of devices currently available for Spotify Connect
+    async fn get_devices(&self) -> Result<Vec<device::Device>> {
+        Ok(Self::handle_rspotify_result(self.spotify.device().await)?.devices)
+    }
+
+    /// transfers playback to the device with the given id
+    async fn transfer_playback(&self, device_id: &str, play: Option<bool>) -> Result<()> {
+        Self::handle_rspotify_result(self.spotify.transfer_playback(device_id, play).await)
+    }
+
+    /// seeks to the given position (in milliseconds) in the current track
+    async fn seek_track(&self, position_ms: u32) -> Result<()> {
+        Self::handle_rspotify_result(self.spotify.seek_track(position_ms, None).await)
+    }
+
+    /// seeks forward/backward relative to the current playback position by
+    /// `delta_ms` milliseconds (negative to seek backward), clamped to the
+    /// current track's duration
+    async fn seek_relative(
+        &self,
+        state: &RwLockReadGuard<'_, state::State>,
+        delta_ms: i32,
+    ) -> Result<()> {
+        let playback = Self::get_current_playback_state(state)?;
+        let duration_ms = playback.item.as_ref().map_or(0, |item| item.duration_ms);
+        let position_ms = (playback.progress_ms.unwrap_or(0) as i64 + delta_ms as i64)
+            .clamp(0, duration_ms as i64) as u32;
+        self.seek_track(position_ms).await
+    }
+
+    /// searches the Spotify catalog for tracks, albums, artists and playlists
+    /// matching `query`
+    async fn search(&self, query: &str) -> Result<SearchResults> {
+        let tracks = match Self::handle_rspotify_result(
+            self.spotify.search_track(query, 20, 0, None).await,
+        )? {
+            rspotify::model::search::SearchResult::Tracks(page) => page.items,
+            _ => vec![],
+        };
+        let albums = match Self::handle_rspotify_result(
+            self.spotify.search_album(query, 20, 0, None).await,
+        )? {
+            rspotify::model::search::SearchResult::Albums(page) => page.items,
+            _ => vec![],
+        };
+        let artists = match Self::handle_rspotify_result(
+            self.spotify.search_artist(query, 20, 0, None).await,
+        )? {
+            rspotify::model::search::SearchResult::Artists(page) => page.items,
+            _ => vec![],
+        };
+        let playlists = match Self::handle_rspotify_result(
+            self.spotify.search_playlist(query, 20, 0, None).await,
+        )? {
+            rspotify::model::search::SearchResult::Playlists(page) => page.items,
+            _ => vec![],
+        };
+        Ok(SearchResults {
+            tracks,
+            albums,
+            artists,
+            playlists,
+        })
+    }
+
+    /// plays the track/album/artist/playlist identified by `uri`: a track uri
+    /// is played directly as a single-track list, while any other uri (album/artist/playlist) is
+    /// started as a playback context
+    async fn play_selected(&self, uri: &str) -> Result<()> {
+        if uri.contains(":track:") {
+            Self::handle_rspotify_result(
+                self.spotify
+                    .start_playback(None, None, Some(vec![uri.to_owned()]), None, None)
+                    .await,
+            )
+        } else {
+            Self::handle_rspotify_result(
+                self.spotify
+                    .start_playback(None, Some(uri.to_owned()), None, None, None)
+                    .await,
+            )
+        }
+    }
+
+    /// fetches recommended tracks seeded from the given tracks/artists/genres,
+    /// returning the `radio:` id the results should be stored under alongside
+    /// the recommended tracks
+    async fn get_recommendations(
+        &self,
+        seed_tracks: Vec<String>,
+        seed_artists: Vec<String>,
+        seed_genres: Vec<String>,
+    ) -> Result<(String, Vec<track::FullTrack>)> {
+        const RECOMMENDATIONS_LIMIT: u32 = 50;
+
+        let uri = seed_tracks
+            .first()
+            .or_else(|| seed_artists.first())
+            .cloned()
+            .unwrap_or_default();
+
+        let recommendations = Self::handle_rspotify_result(
+            self.spotify
+                .recommendations(
+                    seed_artists,
+                    seed_genres,
+                    seed_tracks,
+                    Some(RECOMMENDATIONS_LIMIT),
+                    None,
+                    &Default::default(),
+                )
+                .await,
+        )?;
+
+        Ok((format!("radio:{uri}"), recommendations.tracks))
     }
 
     /// cycles through the repeat state of the current playback
@@ -190,6 +442,9 @@ impl Client {
     where
         T: serde::de::DeserializeOwned,
     {
+        #[cfg(feature = "metrics")]
+        self.metrics.record_api_call();
+
         Ok(self
             .http
             .get(url)
@@ -200,6 +455,72 @@ impl Client {
             .await?)
     }
 
+    /// follows a `page::Page<T>`'s `next` links, accumulating every page's `items`
+    /// into a single `Vec`, retrying rate-limited requests rather than failing
+    async fn fetch_all_pages<T>(&self, first: page::Page<T>) -> Result<Vec<T>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let mut items = first.items;
+        let mut next = first.next;
+        while let Some(url) = next.take() {
+            let page = self.paginate_call::<T>(&url).await?;
+            next = Self::merge_page(&mut items, page.items, page.next);
+        }
+        Ok(items)
+    }
+
+    /// merges a fetched page's items into the accumulator, returning the next
+    /// page's url to follow, or `None` to stop (the page was empty, which the
+    /// API can return instead of a `null` `next` link, or there's no further link)
+    fn merge_page<T>(
+        items: &mut Vec<T>,
+        page_items: Vec<T>,
+        page_next: Option<String>,
+    ) -> Option<String> {
+        if page_items.is_empty() {
+            return None;
+        }
+        items.extend(page_items);
+        page_next
+    }
+
+    /// performs a single paginated GET request, honoring HTTP 429 responses by
+    /// sleeping for the `Retry-After` duration (defaulting to 5 seconds when
+    /// absent) and retrying the same page instead of surfacing an error
+    async fn paginate_call<T>(&self, url: &str) -> Result<page::Page<T>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        loop {
+            #[cfg(feature = "metrics")]
+            self.metrics.record_api_call();
+
+            let response = self
+                .http
+                .get(url)
+                .header(reqwest::header::AUTHORIZATION, self.get_auth_token().await)
+                .send()
+                .await?;
+
+            if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                let retry_after = response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(5);
+                log::info!("rate limited on {}, retrying after {}s", url, retry_after);
+                #[cfg(feature = "metrics")]
+                self.metrics.record_rate_limit_hit();
+                tokio::time::sleep(std::time::Duration::from_secs(retry_after)).await;
+                continue;
+            }
+
+            return Ok(response.json::<page::Page<T>>().await?);
+        }
+    }
+
     fn get_spotify_client(token: TokenInfo) -> Spotify {
         let client_credential = SpotifyClientCredentials::default()
             .token_info(token)
@@ -226,4 +547,72 @@ impl Client {
             None => Err(anyhow!("unable to get the currently playing context")),
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_page_stops_on_an_empty_page() {
+        let mut items = vec![1, 2, 3];
+        let next = Client::merge_page(&mut items, Vec::<i32>::new(), Some("http://next".to_owned()));
+        assert_eq!(next, None);
+        assert_eq!(items, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn merge_page_continues_while_a_next_link_is_present() {
+        let mut items = vec![1, 2];
+        let next = Client::merge_page(&mut items, vec![3, 4], Some("http://next".to_owned()));
+        assert_eq!(next, Some("http://next".to_owned()));
+        assert_eq!(items, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn merge_page_stops_once_there_is_no_next_link() {
+        let mut items = vec![1];
+        let next = Client::merge_page(&mut items, vec![2], None);
+        assert_eq!(next, None);
+        assert_eq!(items, vec![1, 2]);
+    }
+
+    #[test]
+    fn cache_returns_none_for_a_missing_key() {
+        let cache = Cache::new(Duration::from_secs(60));
+        assert!(cache.get("missing").is_none());
+    }
+
+    #[test]
+    fn cache_roundtrips_a_value_before_its_ttl_expires() {
+        let cache = Cache::new(Duration::from_secs(60));
+        cache.set("playlist:1".to_owned(), CachedValue::PlaylistTracks(vec![]));
+        assert!(matches!(
+            cache.get("playlist:1"),
+            Some(CachedValue::PlaylistTracks(_))
+        ));
+    }
+
+    #[test]
+    fn cache_expires_entries_past_their_ttl() {
+        let cache = Cache::new(Duration::from_millis(0));
+        cache.set("playlist:1".to_owned(), CachedValue::PlaylistTracks(vec![]));
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(cache.get("playlist:1").is_none());
+    }
+
+    #[test]
+    fn cache_keys_are_namespaced_by_value_kind() {
+        let cache = Cache::new(Duration::from_secs(60));
+        cache.set("playlist_tracks:1".to_owned(), CachedValue::PlaylistTracks(vec![]));
+        assert!(cache.get("playlist:1").is_none());
+    }
+
+    #[test]
+    fn cache_invalidate_removes_the_entry() {
+        let cache = Cache::new(Duration::from_secs(60));
+        cache.set("playlist:1".to_owned(), CachedValue::PlaylistTracks(vec![]));
+        cache.invalidate("playlist:1");
+        assert!(cache.get("playlist:1").is_none());
+    }
 }
\ No newline at end of file
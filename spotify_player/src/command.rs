@@ -8,6 +8,11 @@ pub enum Command {
     ResumePause,
     Repeat,
     Shuffle,
+    SeekForward,
+    SeekBackward,
+
+    NextSearchResultsCategory,
+    PreviousSearchResultsCategory,
 
     Quit,
     OpenCommandHelp,
@@ -31,4 +36,17 @@ pub enum Command {
     SortByDuration,
     SortByAddedDate,
     ReverseOrder,
+}
+
+impl Command {
+    /// Returns the relative seek delta (in milliseconds) for `SeekForward`/
+    /// `SeekBackward`, using `step_ms` (the user-configurable seek step) as
+    /// the jump size. Returns `None` for commands other than those two.
+    pub fn seek_delta_ms(self, step_ms: u32) -> Option<i32> {
+        match self {
+            Command::SeekForward => Some(step_ms as i32),
+            Command::SeekBackward => Some(-(step_ms as i32)),
+            _ => None,
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,79 @@
+use serde::Deserialize;
+
+/// Application's theme configuration
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Theme {}
+
+/// Configuration for the per-entity response cache
+#[derive(Debug, Clone, Deserialize)]
+pub struct CacheConfig {
+    /// how long (in seconds) a cached entry remains valid
+    #[serde(default = "CacheConfig::default_ttl_secs")]
+    pub ttl_secs: u64,
+}
+
+impl CacheConfig {
+    fn default_ttl_secs() -> u64 {
+        60
+    }
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl_secs: Self::default_ttl_secs(),
+        }
+    }
+}
+
+/// Configuration for playback controls
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlaybackConfig {
+    /// how far (in milliseconds) `SeekForward`/`SeekBackward` jump
+    #[serde(default = "PlaybackConfig::default_seek_step_ms")]
+    pub seek_step_ms: u32,
+}
+
+impl PlaybackConfig {
+    fn default_seek_step_ms() -> u32 {
+        5000
+    }
+}
+
+impl Default for PlaybackConfig {
+    fn default() -> Self {
+        Self {
+            seek_step_ms: Self::default_seek_step_ms(),
+        }
+    }
+}
+
+/// Configuration for the optional `metrics` subsystem
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone, Deserialize)]
+pub struct MetricsConfig {
+    /// URL of the Prometheus Pushgateway to push metrics to
+    pub pushgateway_url: String,
+    /// how often (in seconds) to push metrics
+    #[serde(default = "MetricsConfig::default_push_interval_secs")]
+    pub push_interval_secs: u64,
+}
+
+#[cfg(feature = "metrics")]
+impl MetricsConfig {
+    fn default_push_interval_secs() -> u64 {
+        15
+    }
+}
+
+/// Application's configuration, loaded from the user's config file
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub cache: CacheConfig,
+    #[serde(default)]
+    pub playback: PlaybackConfig,
+    #[cfg(feature = "metrics")]
+    #[serde(default)]
+    pub metrics: Option<MetricsConfig>,
+}
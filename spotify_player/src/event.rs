@@ -0,0 +1,30 @@
+/// Application's event
+#[derive(Debug)]
+pub enum Event {
+    RefreshToken,
+    GetCurrentPlaybackContext,
+    NextSong,
+    PreviousSong,
+    ResumePause,
+    Shuffle,
+    Repeat,
+    Quit,
+    GetPlaylist(String),
+    GetCurrentPlaylistTracks,
+    GetDevices,
+    TransferPlayback(String, bool),
+    SeekTrack(u32),
+    /// seeks relative to the current playback position, by `delta_ms`
+    /// milliseconds (negative to seek backward)
+    SeekRelative(i32),
+    Search(String),
+    /// plays the track/album/artist/playlist identified by the given uri, in
+    /// response to `Command::ChoseSelected` on a `PageState::Search` page
+    PlaySelected(String),
+    GetRecommendations {
+        seed_tracks: Vec<String>,
+        seed_artists: Vec<String>,
+        seed_genres: Vec<String>,
+    },
+    InvalidateCache(String),
+}
@@ -0,0 +1,81 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Counters and gauges recorded by the optional `metrics` subsystem and
+/// periodically pushed to a Prometheus Pushgateway.
+#[derive(Default)]
+pub struct Metrics {
+    events_handled: AtomicU64,
+    api_calls: AtomicU64,
+    rate_limit_hits: AtomicU64,
+    tracks_played: AtomicU64,
+    current_track_id: Mutex<Option<String>>,
+    is_playing: AtomicBool,
+}
+
+impl Metrics {
+    pub fn record_event(&self) {
+        self.events_handled.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_api_call(&self) {
+        self.api_calls.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_rate_limit_hit(&self) {
+        self.rate_limit_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_track_played(&self) {
+        self.tracks_played.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// records the id of the currently playing track (if any) and whether
+    /// playback is active, as gauges alongside the event/call counters
+    pub fn set_playback_state(&self, current_track_id: Option<String>, is_playing: bool) {
+        *self.current_track_id.lock().unwrap() = current_track_id;
+        self.is_playing.store(is_playing, Ordering::Relaxed);
+    }
+
+    fn render(&self) -> String {
+        let mut body = format!(
+            "spotify_player_events_handled {}\n\
+             spotify_player_api_calls {}\n\
+             spotify_player_rate_limit_hits {}\n\
+             spotify_player_tracks_played {}\n\
+             spotify_player_is_playing {}\n",
+            self.events_handled.load(Ordering::Relaxed),
+            self.api_calls.load(Ordering::Relaxed),
+            self.rate_limit_hits.load(Ordering::Relaxed),
+            self.tracks_played.load(Ordering::Relaxed),
+            self.is_playing.load(Ordering::Relaxed) as u8,
+        );
+        if let Some(track_id) = self.current_track_id.lock().unwrap().as_ref() {
+            body.push_str(&format!(
+                "spotify_player_current_track{{track_id=\"{track_id}\"}} 1\n"
+            ));
+        }
+        body
+    }
+
+    /// spawns a background task that pushes the current counters to `pushgateway_url`
+    /// every `interval`
+    pub fn spawn_pusher(self: Arc<Self>, pushgateway_url: String, interval: Duration) {
+        tokio::task::spawn(async move {
+            let client = reqwest::Client::new();
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(err) = client
+                    .post(&pushgateway_url)
+                    .body(self.render())
+                    .send()
+                    .await
+                {
+                    log::warn!("failed to push metrics to {}: {:#}", pushgateway_url, err);
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::prelude::*;
+
+pub mod ui;
+
+pub use ui::UIState;
+
+/// Shared, thread-safe reference to the application state
+pub type SharedState = RwLock<State>;
+
+/// Identifies a radio/ad-hoc tracks context by its (possibly synthetic, e.g. `radio:...`) id
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TracksId {
+    pub id: String,
+    pub name: String,
+}
+
+impl TracksId {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+        }
+    }
+}
+
+/// Identifies a browsable context (e.g. a playlist, album, artist or a radio's tracks)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContextId {
+    Tracks(TracksId),
+}
+
+/// A resolved, browsable context, cached by id in `State::context_cache`
+#[derive(Debug, Clone)]
+pub enum Context {
+    Tracks(Vec<track::FullTrack>),
+}
+
+/// Application's state
+#[derive(Debug)]
+pub struct State {
+    pub is_running: bool,
+
+    pub ui: parking_lot::Mutex<UIState>,
+
+    pub current_playback_context: Option<context::CurrentlyPlaybackContext>,
+    pub current_playlist: Option<playlist::FullPlaylist>,
+    pub current_playlist_tracks: Option<Vec<playlist::PlaylistTrack>>,
+
+    /// Devices available for Spotify Connect, as last reported by `event::Event::GetDevices`
+    pub available_devices: Vec<device::Device>,
+
+    /// Resolved browsing contexts (e.g. radio tracks) keyed by their `ContextId`'s id
+    pub context_cache: HashMap<String, Context>,
+
+    /// Results of the last remote catalog search, if any
+    pub search_results: Option<crate::client::SearchResults>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            is_running: true,
+            ui: parking_lot::Mutex::new(UIState::default()),
+            current_playback_context: None,
+            current_playlist: None,
+            current_playlist_tracks: None,
+            available_devices: Vec::new(),
+            context_cache: HashMap::new(),
+            search_results: None,
+        }
+    }
+}
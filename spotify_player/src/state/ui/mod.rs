@@ -1,4 +1,5 @@
-use crate::{config, key};
+use crate::prelude::*;
+use crate::{client::SearchResults, command::Command, config, event, key};
 
 pub type UIStateGuard<'a> = parking_lot::MutexGuard<'a, UIState>;
 
@@ -55,11 +56,78 @@ impl UIState {
         });
     }
 
+    /// Opens the device-list popup, in response to `Command::SwitchDevice`.
+    pub fn new_device_list_popup(&mut self) {
+        self.popup = Some(PopupState::DeviceList { selected: 0 });
+    }
+
+    /// Handles a command while the device-list popup is open: moves the
+    /// selection for `SelectNext`/`SelectPrevious` (delegating the
+    /// wrap-around math to `PopupState::select_next`/`select_previous`), and
+    /// on `ChoseSelected` closes the popup and returns the `TransferPlayback`
+    /// event to emit.
+    pub fn handle_device_list_popup_command(
+        &mut self,
+        command: Command,
+        available_devices: &[device::Device],
+    ) -> Option<event::Event> {
+        let popup = match &mut self.popup {
+            Some(popup @ PopupState::DeviceList { .. }) => popup,
+            _ => return None,
+        };
+
+        match command {
+            Command::SelectNext => {
+                popup.select_next(available_devices.len());
+                None
+            }
+            Command::SelectPrevious => {
+                popup.select_previous(available_devices.len());
+                None
+            }
+            Command::ChoseSelected => {
+                let selected = match popup {
+                    PopupState::DeviceList { selected } => *selected,
+                    _ => unreachable!("matched above"),
+                };
+                let event = available_devices
+                    .get(selected)
+                    .map(|device| event::Event::TransferPlayback(device.id.clone(), true));
+                self.popup = None;
+                event
+            }
+            _ => None,
+        }
+    }
+
     pub fn new_page(&mut self, page: PageState) {
         self.history.push(page);
         self.popup = None;
     }
 
+    /// Opens a fresh search page, with an empty set of tabbed result categories.
+    pub fn new_search_page(&mut self) {
+        self.new_page(PageState::Search {
+            state: SearchPageUIState::new(),
+        });
+    }
+
+    /// Submits the currently open search popup's query. When the user is on
+    /// the search page, this should be a remote catalog search rather than a
+    /// filter over whatever is already loaded, so the returned `Search` event
+    /// should be sent to the `Client`; on any other page, `None` is returned
+    /// and `search_filtered_items` continues to filter locally as the user types.
+    pub fn submit_search_query(&self) -> Option<event::Event> {
+        let query = match &self.popup {
+            Some(PopupState::Search { query }) => query.clone(),
+            _ => return None,
+        };
+        match self.current_page() {
+            PageState::Search { .. } => Some(event::Event::Search(query)),
+            _ => None,
+        }
+    }
+
     pub fn new_radio_page(&mut self, uri: &str) {
         self.new_page(PageState::Context {
             id: None,
@@ -71,6 +139,91 @@ impl UIState {
         });
     }
 
+    /// Parses a `spotify:track:...` or `spotify:artist:...` uri into the seed
+    /// lists expected by the recommendations endpoint, so starting a radio from
+    /// either a track or an artist works.
+    pub fn radio_seeds_from_uri(uri: &str) -> (Vec<String>, Vec<String>) {
+        match uri.split(':').nth(1) {
+            Some("artist") => (vec![], vec![uri.to_owned()]),
+            _ => (vec![uri.to_owned()], vec![]),
+        }
+    }
+
+    /// Given the x-coordinate of a mouse click inside `playback_progress_bar_rect`
+    /// and the current track's duration, computes the position (in milliseconds)
+    /// to seek to.
+    pub fn position_ms_from_seek_bar_click(&self, click_x: u16, duration_ms: u32) -> u32 {
+        let rect = self.playback_progress_bar_rect;
+        let click_x = click_x.saturating_sub(rect.x).min(rect.width) as u64;
+        (duration_ms as u64 * click_x / rect.width.max(1) as u64) as u32
+    }
+
+    /// Handles a mouse click at `click_x` inside `playback_progress_bar_rect`,
+    /// returning the `SeekTrack` event to emit for the given track duration.
+    pub fn handle_seek_bar_click(&self, click_x: u16, duration_ms: u32) -> event::Event {
+        event::Event::SeekTrack(self.position_ms_from_seek_bar_click(click_x, duration_ms))
+    }
+
+    /// Handles `Command::SeekForward`/`SeekBackward`, returning the
+    /// `SeekRelative` event to emit using the user-configurable seek step.
+    /// Returns `None` for any other command.
+    pub fn handle_seek_command(
+        command: Command,
+        config: &config::PlaybackConfig,
+    ) -> Option<event::Event> {
+        command
+            .seek_delta_ms(config.seek_step_ms)
+            .map(event::Event::SeekRelative)
+    }
+
+    /// Handles a command while the search page is focused: cycles the
+    /// focused category for `NextSearchResultsCategory`/
+    /// `PreviousSearchResultsCategory`, moves the selection within the
+    /// category for `SelectNext`/`SelectPrevious`, and on `ChoseSelected`
+    /// returns the `PlaySelected` event for the currently selected item's uri.
+    pub fn handle_search_page_command(
+        &mut self,
+        command: Command,
+        results: &SearchResults,
+    ) -> Option<event::Event> {
+        let state = match self.current_page_mut() {
+            PageState::Search { state } => state,
+            _ => return None,
+        };
+
+        match command {
+            Command::NextSearchResultsCategory => {
+                state.category = state.category.next();
+                state.selected = 0;
+                None
+            }
+            Command::PreviousSearchResultsCategory => {
+                state.category = state.category.previous();
+                state.selected = 0;
+                None
+            }
+            Command::SelectNext => {
+                let len = state.category.len(results);
+                if len > 0 {
+                    state.selected = (state.selected + 1) % len;
+                }
+                None
+            }
+            Command::SelectPrevious => {
+                let len = state.category.len(results);
+                if len > 0 {
+                    state.selected = (state.selected + len - 1) % len;
+                }
+                None
+            }
+            Command::ChoseSelected => state
+                .category
+                .uri(results, state.selected)
+                .map(event::Event::PlaySelected),
+            _ => None,
+        }
+    }
+
     /// Return whether there exists a focused popup.
     ///
     /// Currently, only search popup is not focused when it's opened.
@@ -129,3 +282,54 @@ impl Default for UIState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seek_bar_click_position_scales_linearly_across_the_rect() {
+        let mut ui = UIState::default();
+        ui.playback_progress_bar_rect = tui::layout::Rect {
+            x: 10,
+            y: 0,
+            width: 100,
+            height: 1,
+        };
+        assert_eq!(ui.position_ms_from_seek_bar_click(10, 200_000), 0);
+        assert_eq!(ui.position_ms_from_seek_bar_click(60, 200_000), 100_000);
+        assert_eq!(ui.position_ms_from_seek_bar_click(110, 200_000), 200_000);
+    }
+
+    #[test]
+    fn seek_bar_click_left_of_the_rect_clamps_to_zero() {
+        let mut ui = UIState::default();
+        ui.playback_progress_bar_rect = tui::layout::Rect {
+            x: 10,
+            y: 0,
+            width: 100,
+            height: 1,
+        };
+        assert_eq!(ui.position_ms_from_seek_bar_click(0, 200_000), 0);
+    }
+
+    #[test]
+    fn seek_bar_click_on_a_zero_width_rect_does_not_panic() {
+        let ui = UIState::default();
+        assert_eq!(ui.position_ms_from_seek_bar_click(0, 200_000), 0);
+    }
+
+    #[test]
+    fn radio_seeds_from_track_uri_seeds_tracks_not_artists() {
+        let (tracks, artists) = UIState::radio_seeds_from_uri("spotify:track:abc");
+        assert_eq!(tracks, vec!["spotify:track:abc".to_owned()]);
+        assert!(artists.is_empty());
+    }
+
+    #[test]
+    fn radio_seeds_from_artist_uri_seeds_artists_not_tracks() {
+        let (tracks, artists) = UIState::radio_seeds_from_uri("spotify:artist:xyz");
+        assert_eq!(artists, vec!["spotify:artist:xyz".to_owned()]);
+        assert!(tracks.is_empty());
+    }
+}
@@ -0,0 +1,138 @@
+use super::*;
+use crate::client::SearchResults;
+
+/// The kind of context being browsed on a `PageState::Context` page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContextPageType {
+    CurrentPlaying,
+    Browsing(ContextId),
+}
+
+#[derive(Debug, Default)]
+pub struct LibraryPageUIState {}
+
+impl LibraryPageUIState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// The category of results focused on a `PageState::Search` page
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SearchResultsCategory {
+    Tracks,
+    Albums,
+    Artists,
+    Playlists,
+}
+
+impl SearchResultsCategory {
+    pub const ALL: [SearchResultsCategory; 4] = [
+        SearchResultsCategory::Tracks,
+        SearchResultsCategory::Albums,
+        SearchResultsCategory::Artists,
+        SearchResultsCategory::Playlists,
+    ];
+
+    pub fn next(self) -> Self {
+        let categories = Self::ALL;
+        let i = categories.iter().position(|c| *c == self).unwrap();
+        categories[(i + 1) % categories.len()]
+    }
+
+    pub fn previous(self) -> Self {
+        let categories = Self::ALL;
+        let i = categories.iter().position(|c| *c == self).unwrap();
+        categories[(i + categories.len() - 1) % categories.len()]
+    }
+
+    /// the number of results currently loaded for this category
+    pub fn len(self, results: &SearchResults) -> usize {
+        match self {
+            SearchResultsCategory::Tracks => results.tracks.len(),
+            SearchResultsCategory::Albums => results.albums.len(),
+            SearchResultsCategory::Artists => results.artists.len(),
+            SearchResultsCategory::Playlists => results.playlists.len(),
+        }
+    }
+
+    /// the uri of the `index`-th result in this category, if any
+    pub fn uri(self, results: &SearchResults, index: usize) -> Option<String> {
+        match self {
+            SearchResultsCategory::Tracks => {
+                results.tracks.get(index).map(|t| t.uri.clone())
+            }
+            SearchResultsCategory::Albums => {
+                results.albums.get(index).and_then(|a| a.uri.clone())
+            }
+            SearchResultsCategory::Artists => {
+                results.artists.get(index).map(|a| a.uri.clone())
+            }
+            SearchResultsCategory::Playlists => {
+                results.playlists.get(index).map(|p| p.uri.clone())
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SearchPageUIState {
+    pub category: SearchResultsCategory,
+    /// the index of the currently selected item within `category`'s results
+    pub selected: usize,
+}
+
+impl SearchPageUIState {
+    pub fn new() -> Self {
+        Self {
+            category: SearchResultsCategory::Tracks,
+            selected: 0,
+        }
+    }
+}
+
+/// Application's page state
+#[derive(Debug)]
+pub enum PageState {
+    Library {
+        state: LibraryPageUIState,
+    },
+    Context {
+        id: Option<String>,
+        context_page_type: ContextPageType,
+        state: Option<()>,
+    },
+    /// Remote catalog search results, tabbed by `SearchResultsCategory`
+    Search {
+        state: SearchPageUIState,
+    },
+}
+
+impl PageState {
+    /// selects the `index`-th item of whatever list this page is currently focused on.
+    /// A no-op for pages without a selectable list (the library and context pages).
+    pub fn select(&mut self, index: usize) {
+        if let PageState::Search { state } = self {
+            state.selected = index;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn category_next_cycles_forward_and_wraps() {
+        assert_eq!(SearchResultsCategory::Tracks.next(), SearchResultsCategory::Albums);
+        assert_eq!(SearchResultsCategory::Albums.next(), SearchResultsCategory::Artists);
+        assert_eq!(SearchResultsCategory::Artists.next(), SearchResultsCategory::Playlists);
+        assert_eq!(SearchResultsCategory::Playlists.next(), SearchResultsCategory::Tracks);
+    }
+
+    #[test]
+    fn category_previous_cycles_backward_and_wraps() {
+        assert_eq!(SearchResultsCategory::Tracks.previous(), SearchResultsCategory::Playlists);
+        assert_eq!(SearchResultsCategory::Playlists.previous(), SearchResultsCategory::Artists);
+    }
+}
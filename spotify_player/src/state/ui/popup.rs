@@ -0,0 +1,30 @@
+use super::*;
+
+/// Application's popup state
+#[derive(Debug)]
+pub enum PopupState {
+    Search { query: String },
+    /// Lists the devices available for Spotify Connect, letting the user
+    /// transfer playback to the selected one
+    DeviceList { selected: usize },
+}
+
+impl PopupState {
+    /// moves the selection of a list-based popup to the next item, wrapping around
+    pub fn select_next(&mut self, len: usize) {
+        if let PopupState::DeviceList { selected } = self {
+            if len > 0 {
+                *selected = (*selected + 1) % len;
+            }
+        }
+    }
+
+    /// moves the selection of a list-based popup to the previous item, wrapping around
+    pub fn select_previous(&mut self, len: usize) {
+        if let PopupState::DeviceList { selected } = self {
+            if len > 0 {
+                *selected = (*selected + len - 1) % len;
+            }
+        }
+    }
+}